@@ -3,21 +3,27 @@ use std::fs;
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct Config {
-    pub kaspad_url: String,
+    pub kaspad_urls: Vec<String>,
     pub port: u16,
     pub faucet_private_key: String,
     pub amount_per_claim: u64,
     pub claim_interval_seconds: u64,
+    pub max_per_ip_per_slice: u64,
+    pub time_slice_seconds: u64,
+    pub confirmation_timeout_seconds: u64,
 }
 
 impl Default for Config {
     fn default() -> Self {
         Self {
-            kaspad_url: "127.0.0.1:16210".to_string(),
+            kaspad_urls: vec!["127.0.0.1:16210".to_string()],
             port: 3010,
             faucet_private_key: String::new(),
             amount_per_claim: 100_000_000, // 0.001 KAS in sompis
             claim_interval_seconds: 3600, // 1 hour
+            max_per_ip_per_slice: 1_000_000_000, // 10 KAS per slice
+            time_slice_seconds: 86_400, // 24h
+            confirmation_timeout_seconds: 30,
         }
     }
 }
@@ -0,0 +1,122 @@
+use crate::kaspad::FailoverClient;
+use kaspa_addresses::Address;
+use kaspa_grpc_client::GrpcClient;
+use kaspa_rpc_core::{
+    api::rpc::RpcApi,
+    notify::{
+        connection::{ChannelConnection, ChannelType},
+        listener::ListenerId,
+        scope::{Scope, UtxosChangedScope},
+    },
+    Notification, RpcTransactionId,
+};
+use serde::Serialize;
+use std::collections::HashMap;
+use std::sync::Arc;
+use tokio::sync::{mpsc, RwLock};
+use tokio::time::{Duration, Instant};
+use tracing::warn;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ConfirmationStatus {
+    Pending,
+    Confirmed,
+    Orphaned,
+}
+
+/// Tracks whether a submitted claim transaction has actually landed in the DAG, by
+/// subscribing to UTXO-changed notifications for the addresses the faucet pays out to and
+/// recording which transaction ids produced a new UTXO. Lets clients poll
+/// `GET /claim/{tx_id}/status` for a reliable signal instead of just "submitted".
+///
+/// Holds a clone of the `GrpcClient` that `FailoverClient` was using at construction, and
+/// keeps it for the tracker's lifetime: the listener is registered against that connection,
+/// and a later failover replaces only the client used for ordinary RPC calls. If that
+/// connection's endpoint goes down, no further notifications arrive; the listener is not
+/// re-registered on the endpoint `FailoverClient` moves to.
+pub struct ConfirmationTracker {
+    rpc_client: GrpcClient,
+    listener_id: ListenerId,
+    statuses: Arc<RwLock<HashMap<RpcTransactionId, ConfirmationStatus>>>,
+}
+
+impl ConfirmationTracker {
+    /// Registers a single notification listener on the connection `client` is using right
+    /// now. Call `watch` for
+    /// each destination address as claims are submitted, to extend the watch set.
+    pub async fn new(client: Arc<FailoverClient>) -> anyhow::Result<Self> {
+        let statuses = Arc::new(RwLock::new(HashMap::new()));
+        let (sender, mut receiver) = mpsc::unbounded_channel();
+        let connection = ChannelConnection::new("faucet-confirmations", sender, ChannelType::Unbounded);
+
+        let rpc_client = client.raw_client().await;
+        let listener_id = rpc_client.register_new_listener(connection);
+
+        let statuses_for_task = statuses.clone();
+        tokio::spawn(async move {
+            while let Some(notification) = receiver.recv().await {
+                if let Notification::UtxosChanged(changed) = notification {
+                    let mut statuses = statuses_for_task.write().await;
+                    for entry in changed.added.iter() {
+                        statuses.insert(entry.outpoint.transaction_id, ConfirmationStatus::Confirmed);
+                    }
+                }
+            }
+        });
+
+        Ok(Self { rpc_client, listener_id, statuses })
+    }
+
+    /// Extends the watch set to include `address` and records `tx_id` as pending
+    /// confirmation.
+    pub async fn watch(&self, address: Address, tx_id: RpcTransactionId) -> anyhow::Result<()> {
+        self.statuses
+            .write()
+            .await
+            .entry(tx_id)
+            .or_insert(ConfirmationStatus::Pending);
+
+        self.rpc_client
+            .start_notify(
+                self.listener_id,
+                Scope::UtxosChanged(UtxosChangedScope { addresses: vec![address] }),
+            )
+            .await
+            .map_err(|e| anyhow::anyhow!("failed to subscribe to UTXO changes: {e}"))?;
+        Ok(())
+    }
+
+    /// Current confirmation status for `tx_id`, defaulting to `Pending` if unknown.
+    pub async fn status(&self, tx_id: &RpcTransactionId) -> ConfirmationStatus {
+        self.statuses
+            .read()
+            .await
+            .get(tx_id)
+            .copied()
+            .unwrap_or(ConfirmationStatus::Pending)
+    }
+
+    /// Polls `status` until `tx_id` is confirmed or `timeout` elapses, at which point the
+    /// transaction is marked `Orphaned`; `status` then reports `Orphaned` until a UTXO-changed
+    /// notification for it arrives. A later call on an `Orphaned` transaction waits out the
+    /// full `timeout` again.
+    pub async fn wait_for_confirmation(
+        &self,
+        tx_id: RpcTransactionId,
+        timeout: Duration,
+    ) -> ConfirmationStatus {
+        let deadline = Instant::now() + timeout;
+        loop {
+            if self.status(&tx_id).await == ConfirmationStatus::Confirmed {
+                return ConfirmationStatus::Confirmed;
+            }
+            if Instant::now() >= deadline {
+                warn!("Claim {tx_id} not confirmed within {:?}, marking orphaned", timeout);
+                self.statuses.write().await.insert(tx_id, ConfirmationStatus::Orphaned);
+                return ConfirmationStatus::Orphaned;
+            }
+            tokio::time::sleep(Duration::from_millis(250)).await;
+        }
+    }
+}
@@ -0,0 +1,51 @@
+use axum::{
+    http::StatusCode,
+    response::{IntoResponse, Response},
+    Json,
+};
+use serde::Serialize;
+use thiserror::Error;
+
+/// Structured failure modes for faucet operations, mapped to precise HTTP statuses so
+/// callers (including automated CI scripts) can react to the specific cause of a failure.
+#[derive(Debug, Error)]
+pub enum FaucetError {
+    #[error("invalid address: {0}")]
+    InvalidAddress(String),
+    #[error("rate limited, retry after {retry_after}s")]
+    RateLimited {
+        retry_after: u64,
+        transaction_id: Option<String>,
+    },
+    #[error("insufficient faucet funds: have {have} sompi, need {need} sompi")]
+    InsufficientFaucetFunds { have: u64, need: u64 },
+    #[error("kaspad node unavailable: {0}")]
+    NodeUnavailable(String),
+    #[error("failed to submit transaction: {0}")]
+    SubmitFailed(String),
+}
+
+#[derive(Serialize)]
+struct ErrorResponse {
+    error: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    transaction_id: Option<String>,
+}
+
+impl IntoResponse for FaucetError {
+    fn into_response(self) -> Response {
+        let status = match &self {
+            FaucetError::InvalidAddress(_) => StatusCode::BAD_REQUEST,
+            FaucetError::RateLimited { .. } => StatusCode::TOO_MANY_REQUESTS,
+            FaucetError::InsufficientFaucetFunds { .. } => StatusCode::SERVICE_UNAVAILABLE,
+            FaucetError::NodeUnavailable(_) => StatusCode::SERVICE_UNAVAILABLE,
+            FaucetError::SubmitFailed(_) => StatusCode::INTERNAL_SERVER_ERROR,
+        };
+        let transaction_id = match &self {
+            FaucetError::RateLimited { transaction_id, .. } => transaction_id.clone(),
+            _ => None,
+        };
+        let error = self.to_string();
+        (status, Json(ErrorResponse { error, transaction_id })).into_response()
+    }
+}
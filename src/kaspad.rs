@@ -0,0 +1,201 @@
+use kaspa_addresses::Address;
+use kaspa_grpc_client::GrpcClient;
+use kaspa_rpc_core::{
+    api::rpc::RpcApi, notify::mode::NotificationMode, RpcTransaction, RpcTransactionId,
+    RpcUtxosByAddressesEntry,
+};
+use std::future::Future;
+use tokio::sync::RwLock;
+use tokio::time::Duration;
+use tracing::{info, warn};
+
+/// RPC calls are attempted this many times (the first try included) against the active
+/// endpoint before the client fails over to the next one in `kaspad_urls`.
+const MAX_RETRIES_PER_ENDPOINT: u32 = 3;
+const BASE_BACKOFF_MS: u64 = 200;
+
+struct FailoverState {
+    index: usize,
+    client: GrpcClient,
+}
+
+/// Thin wrapper around `GrpcClient` that retries failed RPC calls with exponential
+/// backoff and, after `MAX_RETRIES_PER_ENDPOINT` failures, transparently reconnects to the
+/// next healthy endpoint in `kaspad_urls`. A single node restart no longer takes the
+/// faucet down as long as one configured endpoint is reachable.
+pub struct FailoverClient {
+    urls: Vec<String>,
+    state: RwLock<FailoverState>,
+}
+
+impl FailoverClient {
+    pub async fn connect(urls: Vec<String>) -> anyhow::Result<Self> {
+        if urls.is_empty() {
+            anyhow::bail!("kaspad_urls must contain at least one endpoint");
+        }
+        let client = connect_endpoint(&urls[0]).await?;
+        Ok(Self {
+            urls,
+            state: RwLock::new(FailoverState { index: 0, client }),
+        })
+    }
+
+    /// The kaspad endpoint currently serving RPC calls, for surfacing in `StatusResponse`.
+    pub async fn active_endpoint(&self) -> String {
+        let state = self.state.read().await;
+        self.urls[state.index].clone()
+    }
+
+    pub async fn get_utxos_by_addresses(
+        &self,
+        addresses: Vec<Address>,
+    ) -> anyhow::Result<Vec<RpcUtxosByAddressesEntry>> {
+        self.call(|client| {
+            let addresses = addresses.clone();
+            async move { client.get_utxos_by_addresses(addresses).await }
+        })
+        .await
+    }
+
+    pub async fn submit_transaction(
+        &self,
+        transaction: RpcTransaction,
+        allow_orphan: bool,
+    ) -> anyhow::Result<RpcTransactionId> {
+        self.call(|client| {
+            let transaction = transaction.clone();
+            async move { client.submit_transaction(transaction, allow_orphan).await }
+        })
+        .await
+    }
+
+    pub async fn get_balance_by_address(&self, address: Address) -> anyhow::Result<u64> {
+        self.call(|client| {
+            let address = address.clone();
+            async move { client.get_balance_by_address(address).await }
+        })
+        .await
+    }
+
+    async fn current_client(&self) -> GrpcClient {
+        self.state.read().await.client.clone()
+    }
+
+    /// The underlying `GrpcClient` currently in use, for subsystems (like confirmation
+    /// tracking) that need direct access to notification registration. Failing over
+    /// reconnects the client used for ordinary RPC calls but does not re-arm listeners
+    /// registered against a previous `GrpcClient` returned from here.
+    pub async fn raw_client(&self) -> GrpcClient {
+        self.current_client().await
+    }
+
+    async fn call<T, F, Fut>(&self, op: F) -> anyhow::Result<T>
+    where
+        F: Fn(GrpcClient) -> Fut,
+        Fut: Future<Output = kaspa_rpc_core::RpcResult<T>>,
+    {
+        let attempts = MAX_RETRIES_PER_ENDPOINT as usize * self.urls.len();
+        let mut backoff = Duration::from_millis(BASE_BACKOFF_MS);
+        let mut last_err = None;
+
+        for attempt in 0..attempts {
+            let client = self.current_client().await;
+            match op(client).await {
+                Ok(value) => return Ok(value),
+                Err(e) => {
+                    warn!("kaspad RPC call failed (attempt {}): {e}", attempt + 1);
+                    last_err = Some(e);
+
+                    if (attempt + 1) % MAX_RETRIES_PER_ENDPOINT as usize == 0 {
+                        if let Err(e) = self.failover().await {
+                            warn!("failover attempt failed, will keep retrying: {e}");
+                        }
+                        backoff = Duration::from_millis(BASE_BACKOFF_MS);
+                    } else {
+                        tokio::time::sleep(backoff).await;
+                        backoff *= 2;
+                    }
+                }
+            }
+        }
+
+        anyhow::bail!("all kaspad endpoints exhausted, last error: {:?}", last_err)
+    }
+
+    /// Rotates to the next reachable endpoint in `urls`, starting right after the current
+    /// one and wrapping around. Skips past any endpoint that refuses to connect instead of
+    /// giving up on the first failure, so a single dead endpoint in the middle of the list
+    /// doesn't block failover from reaching a later healthy one.
+    async fn failover(&self) -> anyhow::Result<()> {
+        let mut state = self.state.write().await;
+        let start = state.index;
+
+        for offset in 1..=self.urls.len() {
+            let next_index = (start + offset) % self.urls.len();
+            info!(
+                "Failing over kaspad connection from {} to {}",
+                self.urls[start], self.urls[next_index]
+            );
+            match connect_endpoint(&self.urls[next_index]).await {
+                Ok(client) => {
+                    state.index = next_index;
+                    state.client = client;
+                    return Ok(());
+                }
+                Err(e) => {
+                    warn!("Endpoint {} unreachable during failover: {e}", self.urls[next_index]);
+                }
+            }
+        }
+
+        anyhow::bail!("no configured kaspad endpoint is reachable")
+    }
+}
+
+async fn connect_endpoint(url: &str) -> anyhow::Result<GrpcClient> {
+    let grpc_url = normalize_grpc_url(url);
+    info!("Connecting to kaspad at: {}", grpc_url);
+
+    let client = match GrpcClient::connect_with_args(
+        NotificationMode::Direct,
+        grpc_url.clone(),
+        None,
+        true,
+        None,
+        false,
+        Some(500_000),
+        Default::default(),
+    )
+    .await
+    {
+        Ok(c) => {
+            c.start(None).await;
+            c
+        }
+        Err(e) => {
+            warn!(
+                "connect_with_args failed for {grpc_url}, falling back to connect(): {:?}",
+                e
+            );
+            let c = GrpcClient::connect(grpc_url).await?;
+            c.start(None).await;
+            c
+        }
+    };
+
+    let info = client.get_info().await?;
+    info!("Connected to kaspad: {:?}", info);
+
+    Ok(client)
+}
+
+fn normalize_grpc_url(url: &str) -> String {
+    if url.starts_with("grpc://") {
+        url.to_string()
+    } else {
+        format!(
+            "grpc://{}",
+            url.replace("http://", "").replace("https://", "")
+        )
+    }
+}
@@ -1,5 +1,5 @@
 use axum::{
-    extract::State,
+    extract::{Path, Query, State},
     http::StatusCode,
     response::{Html, Json},
     routing::{get, post},
@@ -9,23 +9,35 @@ use kaspa_addresses::{Address, Prefix, Version};
 use kaspa_consensus_core::{
     sign::sign_with_multiple_v2,
     subnets::SUBNETWORK_ID_NATIVE,
-    tx::{SignableTransaction, Transaction, TransactionInput, TransactionOutput, UtxoEntry},
+    tx::{
+        SignableTransaction, Transaction, TransactionInput, TransactionOutpoint,
+        TransactionOutput, UtxoEntry,
+    },
 };
-use kaspa_grpc_client::GrpcClient;
-use kaspa_rpc_core::{api::rpc::RpcApi, notify::mode::NotificationMode, RpcTransaction};
+use kaspa_rpc_core::RpcTransaction;
 use kaspa_txscript::standard::pay_to_address_script;
 use serde::{Deserialize, Serialize};
-use std::{net::SocketAddr, str::FromStr, sync::Arc};
+use std::{net::SocketAddr, str::FromStr, sync::Arc, time::Instant};
 use tokio::net::TcpListener;
 use tokio::time::Duration;
 use tower_http::cors::CorsLayer;
 use tower_http::services::ServeDir;
 use tracing::{error, info, warn};
 
+mod confirmation;
 mod config;
+mod error;
+mod kaspad;
+mod metrics;
 mod rate_limiter;
+mod reservation;
 
+use confirmation::{ConfirmationStatus, ConfirmationTracker};
 use config::Config;
+use error::FaucetError;
+use kaspad::FailoverClient;
+use metrics::Metrics;
+use reservation::UtxoReservation;
 
 const INDEX_HTML: &str = include_str!("../static/index.html");
 
@@ -35,6 +47,7 @@ struct StatusResponse {
     faucet_address: String,
     balance_kas: String,
     next_claim_seconds: u64,
+    active_endpoint: String,
 }
 
 #[derive(Deserialize)]
@@ -43,20 +56,27 @@ struct ClaimRequest {
 }
 
 #[derive(Serialize)]
-struct ClaimResponse {
-    transaction_id: String,
-    amount_kas: String,
-    next_claim_seconds: u64,
+#[serde(tag = "status", rename_all = "snake_case")]
+enum ClaimResponse {
+    Granted {
+        transaction_id: String,
+        amount_kas: String,
+        next_claim_seconds: u64,
+    },
 }
 
 #[derive(Clone)]
 struct AppState {
-    client: GrpcClient,
+    client: Arc<FailoverClient>,
     faucet_address: Address,
     faucet_private_key: [u8; 32],
     amount_per_claim: u64,
     claim_interval_seconds: u64,
     rate_limiter: Arc<rate_limiter::RateLimiter>,
+    reservation: Arc<UtxoReservation>,
+    metrics: Arc<Metrics>,
+    confirmations: Arc<ConfirmationTracker>,
+    confirmation_timeout: Duration,
 }
 
 #[tokio::main]
@@ -75,51 +95,24 @@ async fn main() -> anyhow::Result<()> {
     let (x_only_public_key, _) = public_key.x_only_public_key();
     let faucet_address = Address::new(Prefix::Testnet, Version::PubKey, &x_only_public_key.serialize());
 
-    // Connect to kaspad
-    let grpc_url = if config.kaspad_url.starts_with("grpc://") {
-        config.kaspad_url.clone()
-    } else {
-        format!(
-            "grpc://{}",
-            config
-                .kaspad_url
-                .replace("http://", "")
-                .replace("https://", "")
-        )
-    };
-    info!("Connecting to kaspad at: {}", grpc_url);
-
-    let client = match GrpcClient::connect_with_args(
-        NotificationMode::Direct,
-        grpc_url.clone(),
-        None,
-        true,
-        None,
-        false,
-        Some(500_000),
-        Default::default(),
-    )
-    .await
-    {
-        Ok(c) => {
-            c.start(None).await;
-            c
-        }
-        Err(e) => {
-            warn!("connect_with_args failed, falling back to connect(): {:?}", e);
-            let c = GrpcClient::connect(grpc_url).await?;
-            c.start(None).await;
-            c
-        }
-    };
-
-    let info = client.get_info().await?;
-    info!("Connected to kaspad: {:?}", info);
+    // Connect to the first configured kaspad endpoint; failed RPC calls later fail over
+    // across every configured endpoint
+    let client = Arc::new(FailoverClient::connect(config.kaspad_urls.clone()).await?);
 
     // Simple in-memory rate limiter
-    let rate_limiter = Arc::new(rate_limiter::RateLimiter::new(Duration::from_secs(
-        config.claim_interval_seconds,
-    )));
+    let rate_limiter = Arc::new(rate_limiter::RateLimiter::new(
+        Duration::from_secs(config.claim_interval_seconds),
+        Duration::from_secs(config.time_slice_seconds),
+        config.max_per_ip_per_slice,
+    ));
+
+    // Tracks UTXOs committed to in-flight claims so concurrent requests can't double-spend
+    let reservation = Arc::new(UtxoReservation::new());
+
+    let metrics = Arc::new(Metrics::new()?);
+
+    // Tracks confirmation of submitted claims via UTXO-changed notifications
+    let confirmations = Arc::new(ConfirmationTracker::new(client.clone()).await?);
 
     let state = AppState {
         client,
@@ -128,6 +121,10 @@ async fn main() -> anyhow::Result<()> {
         amount_per_claim: config.amount_per_claim,
         claim_interval_seconds: config.claim_interval_seconds,
         rate_limiter,
+        reservation,
+        metrics,
+        confirmations,
+        confirmation_timeout: Duration::from_secs(config.confirmation_timeout_seconds),
     };
 
     // Build router
@@ -136,6 +133,8 @@ async fn main() -> anyhow::Result<()> {
         .nest_service("/static", ServeDir::new("static"))
         .route("/status", get(status_handler))
         .route("/claim", post(claim_handler))
+        .route("/claim/:tx_id/status", get(claim_status_handler))
+        .route("/metrics", get(metrics_handler))
         .layer(CorsLayer::permissive())
         .with_state(state);
 
@@ -164,95 +163,258 @@ async fn status_handler(
             StatusCode::INTERNAL_SERVER_ERROR
         })?;
 
+    state.metrics.faucet_balance_sompi.set(balance as f64);
+
     Ok(Json(StatusResponse {
         active: true,
         faucet_address: state.faucet_address.to_string(),
         balance_kas: balance.to_string(),
+        active_endpoint: state.client.active_endpoint().await,
         next_claim_seconds: state.claim_interval_seconds,
     }))
 }
 
+async fn metrics_handler(State(state): State<AppState>) -> Result<String, StatusCode> {
+    // Refresh the balance gauge on every scrape -- it must not depend on something else
+    // having hit /status first, or an operator who only scrapes /metrics would see it stuck
+    // at 0 forever.
+    match state
+        .client
+        .get_balance_by_address(state.faucet_address.clone())
+        .await
+    {
+        Ok(balance) => state.metrics.faucet_balance_sompi.set(balance as f64),
+        Err(e) => warn!("Failed to refresh faucet balance for /metrics: {}", e),
+    }
+
+    state.metrics.render().map_err(|e| {
+        error!("Failed to render metrics: {}", e);
+        StatusCode::INTERNAL_SERVER_ERROR
+    })
+}
+
 async fn claim_handler(
     State(state): State<AppState>,
     axum::extract::ConnectInfo(addr): axum::extract::ConnectInfo<SocketAddr>,
     Json(payload): Json<ClaimRequest>,
-) -> Result<Json<ClaimResponse>, StatusCode> {
+) -> Result<Json<ClaimResponse>, FaucetError> {
+    state.metrics.claims_total.inc();
+    let start = Instant::now();
+
+    let result = claim(&state, addr, &payload).await;
+
+    state
+        .metrics
+        .claim_latency_seconds
+        .observe(start.elapsed().as_secs_f64());
+    if let Err(ref e) = result {
+        state.metrics.record_submit_failure(e);
+    }
+    result
+}
+
+async fn claim(
+    state: &AppState,
+    addr: SocketAddr,
+    payload: &ClaimRequest,
+) -> Result<Json<ClaimResponse>, FaucetError> {
     let ip = addr.ip().to_string();
     info!("Claim request from IP: {}, address: {}", ip, payload.address);
 
-    let destination: Address = payload.address.as_str().try_into().map_err(|e| {
-        warn!("Invalid address: {}", e);
-        StatusCode::BAD_REQUEST
-    })?;
+    let destination: Address = payload
+        .address
+        .as_str()
+        .try_into()
+        .map_err(|e| FaucetError::InvalidAddress(format!("{e}")))?;
+
+    // Rate limit check. Returned as an `Err` (HTTP 429), not a 200 with a rejection in the
+    // body, so callers -- including automated CI scripts -- can tell a capped claim apart
+    // from a granted one without parsing the JSON.
+    if !state.rate_limiter.try_claim(&ip, state.amount_per_claim) {
+        state.metrics.rate_limited_total.inc();
+        let err = FaucetError::RateLimited {
+            retry_after: state.claim_interval_seconds,
+            transaction_id: None,
+        };
+        warn!("Rate limit exceeded for IP {}: {}", ip, err);
+
+        // The memo tx is itself a real on-chain spend, so it needs its own cheap throttle --
+        // otherwise an attacker hammering /claim past the cap turns this into a fee-draining
+        // amplifier. Once that's also exceeded, return the plain 429 with no on-chain memo.
+        if !state.rate_limiter.try_memo(&ip) {
+            return Err(err);
+        }
+
+        let memo = format!("faucet: {err}");
+        let tx_id = submit_memo_transaction(
+            &state.client,
+            &state.reservation,
+            &state.faucet_address,
+            &destination,
+            &state.faucet_private_key,
+            &memo,
+        )
+        .await?;
+
+        if let Err(e) = state.confirmations.watch(destination, tx_id).await {
+            warn!("Failed to subscribe to confirmation for memo tx {tx_id}: {e}");
+        }
 
-    // Rate limit check
-    if !state.rate_limiter.try_claim(&ip) {
-        warn!("Rate limit exceeded for IP: {}", ip);
-        return Err(StatusCode::TOO_MANY_REQUESTS);
+        return Err(FaucetError::RateLimited {
+            retry_after: state.claim_interval_seconds,
+            transaction_id: Some(tx_id.to_string()),
+        });
     }
 
     let tx_id = submit_faucet_transaction(
         &state.client,
+        &state.reservation,
         &state.faucet_address,
         &destination,
         state.amount_per_claim,
         &state.faucet_private_key,
     )
-    .await
-    .map_err(|e| {
-        error!("Faucet send failed: {e:?}");
-        StatusCode::INTERNAL_SERVER_ERROR
-    })?;
+    .await?;
+
+    if let Err(e) = state.confirmations.watch(destination, tx_id).await {
+        warn!("Failed to subscribe to confirmation for claim tx {tx_id}: {e}");
+    }
 
-    Ok(Json(ClaimResponse {
+    Ok(Json(ClaimResponse::Granted {
         transaction_id: tx_id.to_string(),
         amount_kas: state.amount_per_claim.to_string(),
         next_claim_seconds: state.claim_interval_seconds,
     }))
 }
 
+#[derive(Serialize)]
+struct ClaimStatusResponse {
+    transaction_id: String,
+    status: ConfirmationStatus,
+}
+
+#[derive(Deserialize)]
+struct ClaimStatusQuery {
+    /// Opt-in long-poll: block until confirmed or `confirmation_timeout_seconds` elapses,
+    /// instead of returning the current status immediately. Off by default so a client
+    /// polling this route repeatedly gets an instant read each time rather than getting
+    /// stuck waiting out the full timeout on every call.
+    #[serde(default)]
+    wait: bool,
+}
+
+async fn claim_status_handler(
+    State(state): State<AppState>,
+    Path(tx_id): Path<String>,
+    Query(query): Query<ClaimStatusQuery>,
+) -> Result<Json<ClaimStatusResponse>, StatusCode> {
+    let tx_id: kaspa_rpc_core::RpcTransactionId = tx_id.parse().map_err(|e| {
+        warn!("Invalid transaction id in status lookup: {}", e);
+        StatusCode::BAD_REQUEST
+    })?;
+
+    let status = if query.wait {
+        state
+            .confirmations
+            .wait_for_confirmation(tx_id, state.confirmation_timeout)
+            .await
+    } else {
+        state.confirmations.status(&tx_id).await
+    };
+
+    Ok(Json(ClaimStatusResponse {
+        transaction_id: tx_id.to_string(),
+        status,
+    }))
+}
+
+const FEE_PER_INPUT_SOMPI: u64 = 2000;
+const DUST_SOMPI: u64 = 1000;
+
 async fn submit_faucet_transaction(
-    client: &GrpcClient,
+    client: &FailoverClient,
+    reservation: &UtxoReservation,
     faucet_address: &Address,
     destination: &Address,
     amount: u64,
     private_key: &[u8; 32],
-) -> anyhow::Result<kaspa_rpc_core::RpcTransactionId> {
+) -> Result<kaspa_rpc_core::RpcTransactionId, FaucetError> {
     let utxos = client
         .get_utxos_by_addresses(vec![faucet_address.clone()])
         .await
-        .map_err(|e| anyhow::anyhow!("get_utxos_by_addresses failed: {e}"))?;
+        .map_err(|e| FaucetError::NodeUnavailable(format!("get_utxos_by_addresses failed: {e}")))?;
 
-    if utxos.is_empty() {
-        anyhow::bail!("Faucet has no UTXOs. Fund address {faucet_address} first.");
-    }
+    let (selected, total_in, token) = reservation
+        .select_and_reserve(utxos, amount, FEE_PER_INPUT_SOMPI)
+        .map_err(|(have, need)| FaucetError::InsufficientFaucetFunds { have, need })?;
 
-    const FEE_PER_INPUT_SOMPI: u64 = 2000;
-    const DUST_SOMPI: u64 = 1000;
+    let outpoints: Vec<TransactionOutpoint> = selected.iter().map(|e| e.outpoint.into()).collect();
+    let result = submit_selected(
+        client,
+        selected,
+        total_in,
+        amount,
+        destination,
+        faucet_address,
+        vec![],
+        private_key,
+    )
+    .await;
+    reservation.release(token, outpoints);
+    result
+}
 
-    let mut selected = Vec::new();
-    let mut total_in: u64 = 0;
+/// Submits a dust-value transaction to `destination` carrying `memo` in the transaction
+/// payload, so a rejected claim still leaves an on-chain, programmatically parseable trace.
+async fn submit_memo_transaction(
+    client: &FailoverClient,
+    reservation: &UtxoReservation,
+    faucet_address: &Address,
+    destination: &Address,
+    private_key: &[u8; 32],
+    memo: &str,
+) -> Result<kaspa_rpc_core::RpcTransactionId, FaucetError> {
+    let utxos = client
+        .get_utxos_by_addresses(vec![faucet_address.clone()])
+        .await
+        .map_err(|e| FaucetError::NodeUnavailable(format!("get_utxos_by_addresses failed: {e}")))?;
 
-    for entry in utxos.into_iter() {
-        let value = entry.utxo_entry.amount;
-        selected.push(entry);
-        total_in = total_in.saturating_add(value);
+    let (selected, total_in, token) = reservation
+        .select_and_reserve(utxos, DUST_SOMPI, FEE_PER_INPUT_SOMPI)
+        .map_err(|(have, need)| FaucetError::InsufficientFaucetFunds { have, need })?;
 
-        let fee = (selected.len() as u64 + 1) * FEE_PER_INPUT_SOMPI;
-        if total_in >= amount.saturating_add(fee) {
-            break;
-        }
-    }
+    let outpoints: Vec<TransactionOutpoint> = selected.iter().map(|e| e.outpoint.into()).collect();
+    let result = submit_selected(
+        client,
+        selected,
+        total_in,
+        DUST_SOMPI,
+        destination,
+        faucet_address,
+        memo.as_bytes().to_vec(),
+        private_key,
+    )
+    .await;
+    reservation.release(token, outpoints);
+    result
+}
 
+/// Builds, signs, and submits a transaction spending `selected` (which together hold
+/// `total_in` sompi), paying `output_amount` to `destination`, change of at least
+/// `DUST_SOMPI` back to `faucet_address` (smaller change is left to the fee), and `payload`
+/// as the transaction payload.
+async fn submit_selected(
+    client: &FailoverClient,
+    selected: Vec<kaspa_rpc_core::RpcUtxosByAddressesEntry>,
+    total_in: u64,
+    output_amount: u64,
+    destination: &Address,
+    faucet_address: &Address,
+    payload: Vec<u8>,
+    private_key: &[u8; 32],
+) -> Result<kaspa_rpc_core::RpcTransactionId, FaucetError> {
     let fee = (selected.len() as u64 + 1) * FEE_PER_INPUT_SOMPI;
-    if total_in < amount.saturating_add(fee) {
-        anyhow::bail!(
-            "Insufficient faucet funds. Have {total_in} sompi, need {} sompi",
-            amount + fee
-        );
-    }
-
-    let mut change = total_in - amount - fee;
+    let mut change = total_in - output_amount - fee;
     if change > 0 && change < DUST_SOMPI {
         change = 0;
     }
@@ -267,17 +429,22 @@ async fn submit_faucet_transaction(
         .collect::<Vec<_>>();
 
     let mut outputs = Vec::new();
-    outputs.push(TransactionOutput::new(amount, pay_to_address_script(destination)));
+    outputs.push(TransactionOutput::new(output_amount, pay_to_address_script(destination)));
     if change > 0 {
         outputs.push(TransactionOutput::new(change, pay_to_address_script(faucet_address)));
     }
 
-    let tx = Transaction::new(0, inputs, outputs, 0, SUBNETWORK_ID_NATIVE, 0, vec![]);
+    let tx = Transaction::new(0, inputs, outputs, 0, SUBNETWORK_ID_NATIVE, 0, payload);
     let entries = selected.into_iter().map(|e| UtxoEntry::from(e.utxo_entry)).collect::<Vec<_>>();
     let signable_tx = SignableTransaction::with_entries(tx, entries);
-    let signed_tx = sign_with_multiple_v2(signable_tx, std::slice::from_ref(private_key)).fully_signed()?;
+    let signed_tx = sign_with_multiple_v2(signable_tx, std::slice::from_ref(private_key))
+        .fully_signed()
+        .map_err(|e| FaucetError::SubmitFailed(format!("{e}")))?;
 
     let rpc_transaction: RpcTransaction = signed_tx.tx.as_ref().into();
-    let tx_id = client.submit_transaction(rpc_transaction, false).await?;
+    let tx_id = client
+        .submit_transaction(rpc_transaction, false)
+        .await
+        .map_err(|e| FaucetError::SubmitFailed(format!("{e}")))?;
     Ok(tx_id)
 }
@@ -0,0 +1,73 @@
+use crate::error::FaucetError;
+use prometheus::{Encoder, Gauge, Histogram, HistogramOpts, IntCounter, IntCounterVec, Opts, Registry, TextEncoder};
+
+/// Prometheus metrics for the faucet, so operators can alert on a draining balance or a
+/// rising failure rate instead of only seeing it in `tracing` logs.
+pub struct Metrics {
+    registry: Registry,
+    pub claims_total: IntCounter,
+    pub rate_limited_total: IntCounter,
+    pub submit_failures_total: IntCounterVec,
+    pub faucet_balance_sompi: Gauge,
+    pub claim_latency_seconds: Histogram,
+}
+
+impl Metrics {
+    pub fn new() -> anyhow::Result<Self> {
+        let registry = Registry::new();
+
+        let claims_total = IntCounter::new("faucet_claims_total", "Total number of claim requests received")?;
+        registry.register(Box::new(claims_total.clone()))?;
+
+        let rate_limited_total = IntCounter::new(
+            "faucet_rate_limited_total",
+            "Total number of claims rejected by the rate limiter",
+        )?;
+        registry.register(Box::new(rate_limited_total.clone()))?;
+
+        let submit_failures_total = IntCounterVec::new(
+            Opts::new("faucet_submit_failures_total", "Total submit failures, by FaucetError variant"),
+            &["reason"],
+        )?;
+        registry.register(Box::new(submit_failures_total.clone()))?;
+
+        let faucet_balance_sompi = Gauge::new("faucet_balance_sompi", "Current faucet balance in sompi")?;
+        registry.register(Box::new(faucet_balance_sompi.clone()))?;
+
+        let claim_latency_seconds = Histogram::with_opts(HistogramOpts::new(
+            "faucet_claim_latency_seconds",
+            "Claim handler latency in seconds",
+        ))?;
+        registry.register(Box::new(claim_latency_seconds.clone()))?;
+
+        Ok(Self {
+            registry,
+            claims_total,
+            rate_limited_total,
+            submit_failures_total,
+            faucet_balance_sompi,
+            claim_latency_seconds,
+        })
+    }
+
+    /// Increments `submit_failures_total` with a label derived from the `FaucetError` variant.
+    pub fn record_submit_failure(&self, err: &FaucetError) {
+        let reason = match err {
+            FaucetError::InvalidAddress(_) => "invalid_address",
+            FaucetError::RateLimited { .. } => "rate_limited",
+            FaucetError::InsufficientFaucetFunds { .. } => "insufficient_funds",
+            FaucetError::NodeUnavailable(_) => "node_unavailable",
+            FaucetError::SubmitFailed(_) => "submit_failed",
+        };
+        self.submit_failures_total.with_label_values(&[reason]).inc();
+    }
+
+    /// Renders all registered metrics in Prometheus text exposition format.
+    pub fn render(&self) -> anyhow::Result<String> {
+        let encoder = TextEncoder::new();
+        let metric_families = self.registry.gather();
+        let mut buffer = Vec::new();
+        encoder.encode(&metric_families, &mut buffer)?;
+        Ok(String::from_utf8(buffer)?)
+    }
+}
@@ -3,26 +3,59 @@ use std::sync::Mutex;
 use std::time::{Duration, Instant};
 
 pub struct RateLimiter {
-    claims: Mutex<HashMap<String, Instant>>,
+    claims: Mutex<HashMap<String, Vec<(Instant, u64)>>>,
+    memo_claims: Mutex<HashMap<String, Instant>>,
     interval: Duration,
+    time_slice: Duration,
+    max_per_ip_per_slice: u64,
 }
 
 impl RateLimiter {
-    pub fn new(interval: Duration) -> Self {
+    pub fn new(interval: Duration, time_slice: Duration, max_per_ip_per_slice: u64) -> Self {
         Self {
             claims: Mutex::new(HashMap::new()),
+            memo_claims: Mutex::new(HashMap::new()),
             interval,
+            time_slice,
+            max_per_ip_per_slice,
         }
     }
 
-    pub fn try_claim(&self, ip: &str) -> bool {
+    /// Returns `true` if `amount` sompi may be granted to `ip` right now, and records the
+    /// grant. Rejects if the last claim is still within `interval`, or if the sum of grants
+    /// within the trailing `time_slice` plus `amount` would exceed `max_per_ip_per_slice`.
+    pub fn try_claim(&self, ip: &str, amount: u64) -> bool {
         let mut claims = self.claims.lock().unwrap();
-        if let Some(last) = claims.get(ip) {
+        let grants = claims.entry(ip.to_string()).or_default();
+
+        if let Some((last, _)) = grants.last() {
+            if last.elapsed() < self.interval {
+                return false;
+            }
+        }
+
+        grants.retain(|(ts, _)| ts.elapsed() < self.time_slice);
+
+        let total: u64 = grants.iter().map(|(_, amount)| amount).sum();
+        if total.saturating_add(amount) > self.max_per_ip_per_slice {
+            return false;
+        }
+
+        grants.push((Instant::now(), amount));
+        true
+    }
+
+    /// Returns `true` if `ip` may be sent a memo transaction right now (at most one per
+    /// `interval`), and records the attempt. Separate from `try_claim` so a caller hammering
+    /// `/claim` past the cap can't use the memo path to drain faucet UTXOs one fee at a time.
+    pub fn try_memo(&self, ip: &str) -> bool {
+        let mut memo_claims = self.memo_claims.lock().unwrap();
+        if let Some(last) = memo_claims.get(ip) {
             if last.elapsed() < self.interval {
                 return false;
             }
         }
-        claims.insert(ip.to_string(), Instant::now());
+        memo_claims.insert(ip.to_string(), Instant::now());
         true
     }
 }
@@ -0,0 +1,94 @@
+use kaspa_consensus_core::tx::TransactionOutpoint;
+use kaspa_rpc_core::RpcUtxosByAddressesEntry;
+use std::collections::HashMap;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+
+/// How long a reservation survives without being explicitly released, so a crashed submit
+/// can't leak a UTXO forever.
+const RESERVATION_TIMEOUT: Duration = Duration::from_secs(30);
+
+/// Opaque handle to one `select_and_reserve` call's reservations. Required by `release` so a
+/// reservation that expired and was re-picked up by a later claim can't be torn down by the
+/// original, now-stale caller.
+pub type ReservationToken = u64;
+
+/// Tracks outpoints committed to an in-flight claim so two concurrent claims never select
+/// the same UTXO, which would otherwise produce conflicting transactions the node rejects.
+pub struct UtxoReservation {
+    reserved: Mutex<HashMap<TransactionOutpoint, (ReservationToken, Instant)>>,
+    next_token: AtomicU64,
+}
+
+impl UtxoReservation {
+    pub fn new() -> Self {
+        Self {
+            reserved: Mutex::new(HashMap::new()),
+            next_token: AtomicU64::new(1),
+        }
+    }
+
+    /// Greedily selects entries from `candidates` (skipping any outpoint already reserved by
+    /// another in-flight claim) until their total value covers `target` plus the fee for the
+    /// inputs selected so far, then reserves the chosen outpoints atomically under a fresh
+    /// token. On success returns the selected entries, their total input value, and the token
+    /// to pass to `release`; on failure returns the input value actually available and the
+    /// amount that would have been needed.
+    pub fn select_and_reserve(
+        &self,
+        candidates: Vec<RpcUtxosByAddressesEntry>,
+        target: u64,
+        fee_per_input: u64,
+    ) -> Result<(Vec<RpcUtxosByAddressesEntry>, u64, ReservationToken), (u64, u64)> {
+        let mut reserved = self.reserved.lock().unwrap();
+        let now = Instant::now();
+
+        let mut selected = Vec::new();
+        let mut total_in: u64 = 0;
+
+        for entry in candidates {
+            let outpoint: TransactionOutpoint = entry.outpoint.into();
+            let still_reserved = reserved
+                .get(&outpoint)
+                .is_some_and(|(_, since)| now.duration_since(*since) < RESERVATION_TIMEOUT);
+            if still_reserved {
+                continue;
+            }
+
+            total_in = total_in.saturating_add(entry.utxo_entry.amount);
+            selected.push(entry);
+
+            let fee = (selected.len() as u64 + 1) * fee_per_input;
+            if total_in >= target.saturating_add(fee) {
+                break;
+            }
+        }
+
+        let fee = (selected.len() as u64 + 1) * fee_per_input;
+        if total_in < target.saturating_add(fee) {
+            return Err((total_in, target + fee));
+        }
+
+        let token = self.next_token.fetch_add(1, Ordering::Relaxed);
+        for entry in &selected {
+            let outpoint: TransactionOutpoint = entry.outpoint.into();
+            reserved.insert(outpoint, (token, now));
+        }
+
+        Ok((selected, total_in, token))
+    }
+
+    /// Releases previously reserved outpoints once the node has accepted or rejected the
+    /// transaction that spent them. Only removes an entry if it's still held under `token`,
+    /// so a release from a claim whose reservation already expired and was re-selected by a
+    /// later claim can't delete that later claim's live reservation out from under it.
+    pub fn release(&self, token: ReservationToken, outpoints: impl IntoIterator<Item = TransactionOutpoint>) {
+        let mut reserved = self.reserved.lock().unwrap();
+        for outpoint in outpoints {
+            if reserved.get(&outpoint).is_some_and(|(owner, _)| *owner == token) {
+                reserved.remove(&outpoint);
+            }
+        }
+    }
+}